@@ -0,0 +1,104 @@
+use std::io::{self, Read, Write};
+
+/// Byte order of an ECOFF object.
+///
+/// SGI/IRIX MIPS objects are big-endian; DECstation/Ultrix MIPS objects are
+/// little-endian. The reader picks one by inspecting `f_magic` and threads it
+/// through every subsequent field access instead of hardcoding `BigEndian`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl Endian {
+    pub fn parse_u16<R: Read>(&self, reader: &mut R) -> io::Result<u16> {
+        let mut buf = [0u8; 2];
+        reader.read_exact(&mut buf)?;
+        Ok(match self {
+            Endian::Big => u16::from_be_bytes(buf),
+            Endian::Little => u16::from_le_bytes(buf),
+        })
+    }
+
+    pub fn parse_i16<R: Read>(&self, reader: &mut R) -> io::Result<i16> {
+        Ok(self.parse_u16(reader)? as i16)
+    }
+
+    pub fn parse_u32<R: Read>(&self, reader: &mut R) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf)?;
+        Ok(match self {
+            Endian::Big => u32::from_be_bytes(buf),
+            Endian::Little => u32::from_le_bytes(buf),
+        })
+    }
+
+    pub fn parse_i32<R: Read>(&self, reader: &mut R) -> io::Result<i32> {
+        Ok(self.parse_u32(reader)? as i32)
+    }
+
+    pub fn write_u16<W: Write>(&self, writer: &mut W, value: u16) -> io::Result<()> {
+        writer.write_all(&match self {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        })
+    }
+
+    pub fn write_i16<W: Write>(&self, writer: &mut W, value: i16) -> io::Result<()> {
+        self.write_u16(writer, value as u16)
+    }
+
+    pub fn write_u32<W: Write>(&self, writer: &mut W, value: u32) -> io::Result<()> {
+        writer.write_all(&match self {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        })
+    }
+
+    pub fn write_i32<W: Write>(&self, writer: &mut W, value: i32) -> io::Result<()> {
+        self.write_u32(writer, value as u32)
+    }
+
+    /// Extract a C-bitfield member from a 32-bit `word`.
+    ///
+    /// `widths` lists the member widths in declaration order. A big-endian
+    /// target packs the first member into the most significant bits; a
+    /// little-endian target packs it into the least significant bits, so the
+    /// same member lives in opposite positions on the two MIPS variants.
+    /// `index` selects which member to return.
+    pub fn unpack(&self, word: u32, widths: &[u32], index: usize) -> u32 {
+        let shift = self.bitfield_shift(widths, index);
+        (word >> shift) & mask(widths[index])
+    }
+
+    /// Pack C-bitfield members back into a 32-bit word, the inverse of
+    /// [`Endian::unpack`]. `members` are `(width, value)` pairs in declaration
+    /// order.
+    pub fn pack(&self, members: &[(u32, u32)]) -> u32 {
+        let widths: Vec<u32> = members.iter().map(|&(width, _)| width).collect();
+        let mut word = 0;
+        for (index, &(width, value)) in members.iter().enumerate() {
+            word |= (value & mask(width)) << self.bitfield_shift(&widths, index);
+        }
+        word
+    }
+
+    /// Bit offset of member `index` from the least significant bit, given the
+    /// declaration-order widths and this byte order.
+    fn bitfield_shift(&self, widths: &[u32], index: usize) -> u32 {
+        match self {
+            Endian::Big => widths[index + 1..].iter().sum(),
+            Endian::Little => widths[..index].iter().sum(),
+        }
+    }
+}
+
+/// Low-bit mask for a field `width` bits wide.
+fn mask(width: u32) -> u32 {
+    if width >= 32 {
+        u32::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
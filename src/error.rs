@@ -0,0 +1,38 @@
+use std::io;
+
+use thiserror::Error;
+
+/// Errors produced while parsing an ECOFF object.
+///
+/// Parsers capture the stream position at which the failing record starts so
+/// callers can report "bad record at offset 0x…" instead of a bare `UnexpectedEof`.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("unrecognized MIPS ECOFF magic {:#06x}", u16::from_be_bytes(*.0))]
+    BadMagic([u8; 2]),
+
+    #[error("unexpected end of file at offset {offset:#x}")]
+    UnexpectedEof { offset: u64 },
+
+    #[error("string offset {iss} out of range (table is {table_len} bytes)")]
+    StringOffsetOutOfRange { iss: u32, table_len: usize },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Error {
+    /// Attach a byte offset to an end-of-file error captured while reading a
+    /// record, leaving other errors untouched.
+    pub fn at(self, offset: u64) -> Error {
+        match self {
+            Error::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Error::UnexpectedEof { offset }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Convenience alias used throughout the crate.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,1050 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+mod endian;
+mod error;
+mod record;
+
+pub use endian::Endian;
+pub use error::{Error, Result};
+pub use record::{Record, RecordReader};
+
+/// `f_magic` of a big-endian (SGI/IRIX) MIPS ECOFF object.
+pub const MIPSEB_MAGIC: u16 = 0x0160;
+/// `f_magic` of a little-endian (DECstation/Ultrix) MIPS ECOFF object.
+pub const MIPSEL_MAGIC: u16 = 0x0162;
+
+/// Parse a structure from a seekable byte stream, returning the struct.
+///
+/// Modeled after the reader traits decomp-toolkit grew once it dropped
+/// `byteorder`/`binrw`: every record knows how to read itself, so the crate
+/// composes instead of funneling everything through a single `main`. The
+/// `endian` is selected once from `f_magic` and passed down to every record.
+pub trait FromReader: Sized {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self>;
+}
+
+/// Serialize a structure back to a byte stream.
+pub trait ToWriter {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()>;
+}
+
+/// Peek at `f_magic` to choose a byte order, then rewind so the caller can read
+/// the header normally. Returns an error on an unrecognized magic.
+pub fn detect_endian<R: Read + Seek>(reader: &mut R) -> Result<Endian> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    reader.seek(SeekFrom::Current(-2))?;
+    if u16::from_be_bytes(buf) == MIPSEB_MAGIC {
+        Ok(Endian::Big)
+    } else if u16::from_le_bytes(buf) == MIPSEL_MAGIC {
+        Ok(Endian::Little)
+    } else {
+        Err(Error::BadMagic(buf))
+    }
+}
+
+#[derive(Debug)]
+pub struct EcoffFileHeader {
+    pub f_magic: u16,
+    pub f_nscns: u16,
+    pub f_timdat: u32,
+    pub f_symptr: u32,
+    pub f_nsyms: u32,
+    pub f_opthdr: u16,
+    pub f_flags: u16,
+}
+
+impl FromReader for EcoffFileHeader {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        Ok(EcoffFileHeader {
+            f_magic: endian.parse_u16(reader)?,
+            f_nscns: endian.parse_u16(reader)?,
+            f_timdat: endian.parse_u32(reader)?,
+            f_symptr: endian.parse_u32(reader)?,
+            f_nsyms: endian.parse_u32(reader)?,
+            f_opthdr: endian.parse_u16(reader)?,
+            f_flags: endian.parse_u16(reader)?,
+        })
+    }
+}
+
+impl ToWriter for EcoffFileHeader {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u16(writer, self.f_magic)?;
+        endian.write_u16(writer, self.f_nscns)?;
+        endian.write_u32(writer, self.f_timdat)?;
+        endian.write_u32(writer, self.f_symptr)?;
+        endian.write_u32(writer, self.f_nsyms)?;
+        endian.write_u16(writer, self.f_opthdr)?;
+        endian.write_u16(writer, self.f_flags)?;
+        Ok(())
+    }
+}
+
+/// On-disk size of an `EcoffFileHeader`.
+pub const FILE_HEADER_SIZE: u32 = 20;
+
+#[derive(Debug)]
+pub struct EcoffOptionalHeader {
+    pub magic: u16,
+    pub vstamp: u16,
+    pub tsize: u32,
+    pub dsize: u32,
+    pub bsize: u32,
+    pub entry: u32,
+    pub text_start: u32,
+    pub data_start: u32,
+    pub bss_start: u32,
+    pub gprmask: u32,
+    pub cprmask: [u32; 4],
+    pub gp_value: u32,
+}
+
+impl FromReader for EcoffOptionalHeader {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        Ok(EcoffOptionalHeader {
+            magic: endian.parse_u16(reader)?,
+            vstamp: endian.parse_u16(reader)?,
+            tsize: endian.parse_u32(reader)?,
+            dsize: endian.parse_u32(reader)?,
+            bsize: endian.parse_u32(reader)?,
+            entry: endian.parse_u32(reader)?,
+            text_start: endian.parse_u32(reader)?,
+            data_start: endian.parse_u32(reader)?,
+            bss_start: endian.parse_u32(reader)?,
+            gprmask: endian.parse_u32(reader)?,
+            cprmask: [
+                endian.parse_u32(reader)?,
+                endian.parse_u32(reader)?,
+                endian.parse_u32(reader)?,
+                endian.parse_u32(reader)?,
+            ],
+            gp_value: endian.parse_u32(reader)?,
+        })
+    }
+}
+
+impl ToWriter for EcoffOptionalHeader {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u16(writer, self.magic)?;
+        endian.write_u16(writer, self.vstamp)?;
+        endian.write_u32(writer, self.tsize)?;
+        endian.write_u32(writer, self.dsize)?;
+        endian.write_u32(writer, self.bsize)?;
+        endian.write_u32(writer, self.entry)?;
+        endian.write_u32(writer, self.text_start)?;
+        endian.write_u32(writer, self.data_start)?;
+        endian.write_u32(writer, self.bss_start)?;
+        endian.write_u32(writer, self.gprmask)?;
+        for word in self.cprmask {
+            endian.write_u32(writer, word)?;
+        }
+        endian.write_u32(writer, self.gp_value)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct EcoffSectionHeader {
+    pub s_name: [u8; 8],
+    pub s_paddr: u32,
+    pub s_vaddr: u32,
+    pub s_size: u32,
+    pub s_scnptr: u32,
+    pub s_relptr: u32,
+    pub s_lnnoptr: u32,
+    pub s_nreloc: u16,
+    pub s_nlnno: u16,
+    pub s_flags: u32,
+}
+
+impl FromReader for EcoffSectionHeader {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        let mut s_name = [0u8; 8];
+        reader.read_exact(&mut s_name)?;
+
+        Ok(EcoffSectionHeader {
+            s_name,
+            s_paddr: endian.parse_u32(reader)?,
+            s_vaddr: endian.parse_u32(reader)?,
+            s_size: endian.parse_u32(reader)?,
+            s_scnptr: endian.parse_u32(reader)?,
+            s_relptr: endian.parse_u32(reader)?,
+            s_lnnoptr: endian.parse_u32(reader)?,
+            s_nreloc: endian.parse_u16(reader)?,
+            s_nlnno: endian.parse_u16(reader)?,
+            s_flags: endian.parse_u32(reader)?,
+        })
+    }
+}
+
+impl ToWriter for EcoffSectionHeader {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        writer.write_all(&self.s_name)?;
+        endian.write_u32(writer, self.s_paddr)?;
+        endian.write_u32(writer, self.s_vaddr)?;
+        endian.write_u32(writer, self.s_size)?;
+        endian.write_u32(writer, self.s_scnptr)?;
+        endian.write_u32(writer, self.s_relptr)?;
+        endian.write_u32(writer, self.s_lnnoptr)?;
+        endian.write_u16(writer, self.s_nreloc)?;
+        endian.write_u16(writer, self.s_nlnno)?;
+        endian.write_u32(writer, self.s_flags)?;
+        Ok(())
+    }
+}
+
+/// On-disk size of an `EcoffSectionHeader`.
+pub const SECTION_HEADER_SIZE: u32 = 40;
+
+#[derive(Debug)]
+pub struct SymbolHeader {
+    pub magic: u16,
+    pub vstamp: u16,
+    pub iline_max: u32,
+    pub cb_line: u32,
+    pub cb_line_offset: u32,
+    pub idn_max: u32,
+    pub cb_dn_offset: u32,
+    pub ipd_max: u32,
+    pub cb_pd_offset: u32,
+    pub isym_max: u32,
+    pub cb_sym_offset: u32, // Byte offset to start of local symbols.
+    pub iopt_max: u32,
+    pub cb_opt_offset: u32,
+    pub iaux_max: u32,
+    pub cb_aux_offset: u32,
+    pub iss_max: u32, // Byte size of local string table
+    pub cb_ss_offset: u32,
+    pub iss_ext_max: u32,
+    pub cb_ss_ext_offset: u32,
+    pub ifd_max: u32,
+    pub cb_fd_offset: u32,
+    pub crfd: u32,
+    pub cb_rfd_offset: u32,
+    pub iext_max: u32,
+    pub cb_ext_offset: u32,
+    // Machine dependent fields go here if needed
+}
+
+impl FromReader for SymbolHeader {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        Ok(SymbolHeader {
+            magic: endian.parse_u16(reader)?,
+            vstamp: endian.parse_u16(reader)?,
+            iline_max: endian.parse_u32(reader)?,
+            cb_line: endian.parse_u32(reader)?,
+            cb_line_offset: endian.parse_u32(reader)?,
+            idn_max: endian.parse_u32(reader)?,
+            cb_dn_offset: endian.parse_u32(reader)?,
+            ipd_max: endian.parse_u32(reader)?,
+            cb_pd_offset: endian.parse_u32(reader)?,
+            isym_max: endian.parse_u32(reader)?,
+            cb_sym_offset: endian.parse_u32(reader)?,
+            iopt_max: endian.parse_u32(reader)?,
+            cb_opt_offset: endian.parse_u32(reader)?,
+            iaux_max: endian.parse_u32(reader)?,
+            cb_aux_offset: endian.parse_u32(reader)?,
+            iss_max: endian.parse_u32(reader)?, // Byte size of local string table.
+            cb_ss_offset: endian.parse_u32(reader)?, // Byte offset to start of local strings.
+            iss_ext_max: endian.parse_u32(reader)?, // Byte size of external string table.
+            cb_ss_ext_offset: endian.parse_u32(reader)?, // Byte offset to start of external strings.
+            ifd_max: endian.parse_u32(reader)?,
+            cb_fd_offset: endian.parse_u32(reader)?, // Byte offset to start of file descriptors.
+            crfd: endian.parse_u32(reader)?,
+            cb_rfd_offset: endian.parse_u32(reader)?,
+            iext_max: endian.parse_u32(reader)?, // Number of external symbols.
+            cb_ext_offset: endian.parse_u32(reader)?, // Byte offset to start of external symbols.
+        })
+    }
+}
+
+impl ToWriter for SymbolHeader {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u16(writer, self.magic)?;
+        endian.write_u16(writer, self.vstamp)?;
+        endian.write_u32(writer, self.iline_max)?;
+        endian.write_u32(writer, self.cb_line)?;
+        endian.write_u32(writer, self.cb_line_offset)?;
+        endian.write_u32(writer, self.idn_max)?;
+        endian.write_u32(writer, self.cb_dn_offset)?;
+        endian.write_u32(writer, self.ipd_max)?;
+        endian.write_u32(writer, self.cb_pd_offset)?;
+        endian.write_u32(writer, self.isym_max)?;
+        endian.write_u32(writer, self.cb_sym_offset)?;
+        endian.write_u32(writer, self.iopt_max)?;
+        endian.write_u32(writer, self.cb_opt_offset)?;
+        endian.write_u32(writer, self.iaux_max)?;
+        endian.write_u32(writer, self.cb_aux_offset)?;
+        endian.write_u32(writer, self.iss_max)?;
+        endian.write_u32(writer, self.cb_ss_offset)?;
+        endian.write_u32(writer, self.iss_ext_max)?;
+        endian.write_u32(writer, self.cb_ss_ext_offset)?;
+        endian.write_u32(writer, self.ifd_max)?;
+        endian.write_u32(writer, self.cb_fd_offset)?;
+        endian.write_u32(writer, self.crfd)?;
+        endian.write_u32(writer, self.cb_rfd_offset)?;
+        endian.write_u32(writer, self.iext_max)?;
+        endian.write_u32(writer, self.cb_ext_offset)?;
+        Ok(())
+    }
+}
+
+/// On-disk size of a `SymbolHeader`.
+pub const SYMBOL_HEADER_SIZE: u32 = 96;
+
+/// A local symbol record (SYMR), 12 bytes on disk.
+#[derive(Debug)]
+pub struct Symr {
+    /// Index into the local string table of this symbol's name.
+    pub iss: i32,
+    pub value: u32,
+    /// Symbol type (`st`), 6 bits.
+    pub st: u8,
+    /// Storage class (`sc`), 5 bits.
+    pub sc: u8,
+    /// Index into one of the tables, meaning depends on `st`/`sc`. 20 bits.
+    pub index: u32,
+}
+
+impl FromReader for Symr {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        let iss = endian.parse_i32(reader)?;
+        let value = endian.parse_u32(reader)?;
+        let bits = endian.parse_u32(reader)?;
+        Ok(Symr {
+            iss,
+            value,
+            st: endian.unpack(bits, SYMR_FIELDS, 0) as u8,
+            sc: endian.unpack(bits, SYMR_FIELDS, 1) as u8,
+            index: endian.unpack(bits, SYMR_FIELDS, 3),
+        })
+    }
+}
+
+/// Declaration-order widths of the packed SYMR word: `st:6`, `sc:5`,
+/// `reserved:1`, `index:20`.
+const SYMR_FIELDS: &[u32] = &[6, 5, 1, 20];
+
+impl ToWriter for Symr {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_i32(writer, self.iss)?;
+        endian.write_u32(writer, self.value)?;
+        let bits = endian.pack(&[
+            (SYMR_FIELDS[0], self.st as u32),
+            (SYMR_FIELDS[1], self.sc as u32),
+            (SYMR_FIELDS[2], 0),
+            (SYMR_FIELDS[3], self.index),
+        ]);
+        endian.write_u32(writer, bits)?;
+        Ok(())
+    }
+}
+
+/// An external symbol record (EXTR): flags, file index, and an embedded SYMR.
+#[derive(Debug)]
+pub struct Extr {
+    pub flags: u16,
+    pub ifd: i16,
+    pub asym: Symr,
+}
+
+impl Extr {
+    /// True if the symbol is referenced through the jump table.
+    pub fn jmptbl(&self) -> bool {
+        self.flags & 0x8000 != 0
+    }
+
+    /// True if the symbol is a weak external.
+    pub fn weakext(&self) -> bool {
+        self.flags & 0x4000 != 0
+    }
+}
+
+impl FromReader for Extr {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        let flags = endian.parse_u16(reader)?;
+        let _reserved = endian.parse_u16(reader)?;
+        let ifd = endian.parse_i16(reader)?;
+        let asym = Symr::from_reader(reader, endian)?;
+        Ok(Extr { flags, ifd, asym })
+    }
+}
+
+impl ToWriter for Extr {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u16(writer, self.flags)?;
+        endian.write_u16(writer, 0)?; // reserved
+        endian.write_i16(writer, self.ifd)?;
+        self.asym.to_writer(writer, endian)
+    }
+}
+
+/// A file descriptor record (FDR).
+#[derive(Debug)]
+pub struct Fdr {
+    pub adr: u32,
+    pub rss: i32,
+    pub iss_base: i32,
+    pub cb_ss: i32,
+    pub isym_base: i32,
+    pub csym: i32,
+    pub iline_base: i32,
+    pub cline: i32,
+    pub iopt_base: i32,
+    pub copt: i32,
+    pub ipd_first: i32,
+    pub cpd: i32,
+    pub iaux_base: i32,
+    pub caux: i32,
+    pub rfd_base: i32,
+    pub crfd: i32,
+    /// Source language (`lang`), 5 bits.
+    pub lang: u8,
+    pub f_merge: bool,
+    pub f_readin: bool,
+    pub f_bigendian: bool,
+    /// Debug level (`glevel`), 2 bits.
+    pub glevel: u8,
+    pub cb_line_offset: i32,
+    pub cb_line: i32,
+}
+
+impl FromReader for Fdr {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        let adr = endian.parse_u32(reader)?;
+        let rss = endian.parse_i32(reader)?;
+        let iss_base = endian.parse_i32(reader)?;
+        let cb_ss = endian.parse_i32(reader)?;
+        let isym_base = endian.parse_i32(reader)?;
+        let csym = endian.parse_i32(reader)?;
+        let iline_base = endian.parse_i32(reader)?;
+        let cline = endian.parse_i32(reader)?;
+        let iopt_base = endian.parse_i32(reader)?;
+        let copt = endian.parse_i32(reader)?;
+        let ipd_first = endian.parse_i32(reader)?;
+        let cpd = endian.parse_i32(reader)?;
+        let iaux_base = endian.parse_i32(reader)?;
+        let caux = endian.parse_i32(reader)?;
+        let rfd_base = endian.parse_i32(reader)?;
+        let crfd = endian.parse_i32(reader)?;
+        let bits = endian.parse_u32(reader)?;
+        let cb_line_offset = endian.parse_i32(reader)?;
+        let cb_line = endian.parse_i32(reader)?;
+        Ok(Fdr {
+            adr,
+            rss,
+            iss_base,
+            cb_ss,
+            isym_base,
+            csym,
+            iline_base,
+            cline,
+            iopt_base,
+            copt,
+            ipd_first,
+            cpd,
+            iaux_base,
+            caux,
+            rfd_base,
+            crfd,
+            lang: endian.unpack(bits, FDR_FIELDS, 0) as u8,
+            f_merge: endian.unpack(bits, FDR_FIELDS, 1) != 0,
+            f_readin: endian.unpack(bits, FDR_FIELDS, 2) != 0,
+            f_bigendian: endian.unpack(bits, FDR_FIELDS, 3) != 0,
+            glevel: endian.unpack(bits, FDR_FIELDS, 4) as u8,
+            cb_line_offset,
+            cb_line,
+        })
+    }
+}
+
+/// Declaration-order widths of the packed FDR word: `lang:5`, `fMerge:1`,
+/// `fReadin:1`, `fBigendian:1`, `glevel:2`, then 22 reserved bits.
+const FDR_FIELDS: &[u32] = &[5, 1, 1, 1, 2, 22];
+
+impl ToWriter for Fdr {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u32(writer, self.adr)?;
+        endian.write_i32(writer, self.rss)?;
+        endian.write_i32(writer, self.iss_base)?;
+        endian.write_i32(writer, self.cb_ss)?;
+        endian.write_i32(writer, self.isym_base)?;
+        endian.write_i32(writer, self.csym)?;
+        endian.write_i32(writer, self.iline_base)?;
+        endian.write_i32(writer, self.cline)?;
+        endian.write_i32(writer, self.iopt_base)?;
+        endian.write_i32(writer, self.copt)?;
+        endian.write_i32(writer, self.ipd_first)?;
+        endian.write_i32(writer, self.cpd)?;
+        endian.write_i32(writer, self.iaux_base)?;
+        endian.write_i32(writer, self.caux)?;
+        endian.write_i32(writer, self.rfd_base)?;
+        endian.write_i32(writer, self.crfd)?;
+        let bits = endian.pack(&[
+            (FDR_FIELDS[0], self.lang as u32),
+            (FDR_FIELDS[1], self.f_merge as u32),
+            (FDR_FIELDS[2], self.f_readin as u32),
+            (FDR_FIELDS[3], self.f_bigendian as u32),
+            (FDR_FIELDS[4], self.glevel as u32),
+            (FDR_FIELDS[5], 0),
+        ]);
+        endian.write_u32(writer, bits)?;
+        endian.write_i32(writer, self.cb_line_offset)?;
+        endian.write_i32(writer, self.cb_line)?;
+        Ok(())
+    }
+}
+
+/// A procedure descriptor record (PDR).
+#[derive(Debug)]
+pub struct Pdr {
+    pub adr: u32,
+    pub isym: i32,
+    pub iline: i32,
+    pub regmask: u32,
+    pub regoffset: i32,
+    pub iopt: i32,
+    pub fregmask: u32,
+    pub fregoffset: i32,
+    pub frameoffset: i32,
+    pub framereg: i16,
+    pub pcreg: i16,
+    pub ln_low: i32,
+    pub ln_high: i32,
+    pub cb_line_offset: i32,
+}
+
+impl FromReader for Pdr {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        Ok(Pdr {
+            adr: endian.parse_u32(reader)?,
+            isym: endian.parse_i32(reader)?,
+            iline: endian.parse_i32(reader)?,
+            regmask: endian.parse_u32(reader)?,
+            regoffset: endian.parse_i32(reader)?,
+            iopt: endian.parse_i32(reader)?,
+            fregmask: endian.parse_u32(reader)?,
+            fregoffset: endian.parse_i32(reader)?,
+            frameoffset: endian.parse_i32(reader)?,
+            framereg: endian.parse_i16(reader)?,
+            pcreg: endian.parse_i16(reader)?,
+            ln_low: endian.parse_i32(reader)?,
+            ln_high: endian.parse_i32(reader)?,
+            cb_line_offset: endian.parse_i32(reader)?,
+        })
+    }
+}
+
+impl ToWriter for Pdr {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u32(writer, self.adr)?;
+        endian.write_i32(writer, self.isym)?;
+        endian.write_i32(writer, self.iline)?;
+        endian.write_u32(writer, self.regmask)?;
+        endian.write_i32(writer, self.regoffset)?;
+        endian.write_i32(writer, self.iopt)?;
+        endian.write_u32(writer, self.fregmask)?;
+        endian.write_i32(writer, self.fregoffset)?;
+        endian.write_i32(writer, self.frameoffset)?;
+        endian.write_i16(writer, self.framereg)?;
+        endian.write_i16(writer, self.pcreg)?;
+        endian.write_i32(writer, self.ln_low)?;
+        endian.write_i32(writer, self.ln_high)?;
+        endian.write_i32(writer, self.cb_line_offset)?;
+        Ok(())
+    }
+}
+
+/// Slice a NUL-terminated name out of a string table, starting at `iss`.
+///
+/// Returns [`Error::StringOffsetOutOfRange`] rather than panicking when a
+/// symbol's `iss` points past the end of the table.
+fn string_at(table: &[u8], iss: i32) -> Result<&[u8]> {
+    let start = iss as usize;
+    if iss < 0 || start >= table.len() {
+        return Err(Error::StringOffsetOutOfRange {
+            iss: iss as u32,
+            table_len: table.len(),
+        });
+    }
+    let end = table[start..]
+        .iter()
+        .position(|&b| b == 0)
+        .map_or(table.len(), |n| start + n);
+    Ok(&table[start..end])
+}
+
+/// A relocation entry (MIPS ECOFF): a virtual address plus a packed word
+/// carrying the referenced symbol, the relocation type, and the extern/offset
+/// flags that distinguish a symbol-table index from an in-section offset.
+#[derive(Debug)]
+pub struct Relocation {
+    pub r_vaddr: u32,
+    /// Symbol-table index (or section number when `r_extern` is false). 24 bits.
+    pub r_symndx: u32,
+    /// Relocation type. 6 bits.
+    pub r_type: u8,
+    /// `r_symndx` refers to an external symbol rather than a section.
+    pub r_extern: bool,
+    /// The relocation applies to a local offset.
+    pub r_offset: bool,
+}
+
+impl FromReader for Relocation {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        let r_vaddr = endian.parse_u32(reader)?;
+        let bits = endian.parse_u32(reader)?;
+        Ok(Relocation {
+            r_vaddr,
+            r_symndx: endian.unpack(bits, RELOC_FIELDS, 0),
+            r_extern: endian.unpack(bits, RELOC_FIELDS, 1) != 0,
+            r_offset: endian.unpack(bits, RELOC_FIELDS, 2) != 0,
+            r_type: endian.unpack(bits, RELOC_FIELDS, 3) as u8,
+        })
+    }
+}
+
+/// Declaration-order widths of the packed relocation word: `r_symndx:24`,
+/// `r_extern:1`, `r_offset:1`, `r_type:6`.
+const RELOC_FIELDS: &[u32] = &[24, 1, 1, 6];
+
+impl ToWriter for Relocation {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u32(writer, self.r_vaddr)?;
+        let bits = endian.pack(&[
+            (RELOC_FIELDS[0], self.r_symndx),
+            (RELOC_FIELDS[1], self.r_extern as u32),
+            (RELOC_FIELDS[2], self.r_offset as u32),
+            (RELOC_FIELDS[3], self.r_type as u32),
+        ]);
+        endian.write_u32(writer, bits)?;
+        Ok(())
+    }
+}
+
+/// A line-number entry mapping a text address to a source line.
+#[derive(Debug)]
+pub struct LineNumber {
+    /// Symbol index or physical address, depending on `l_lnno`.
+    pub l_addr: u32,
+    pub l_lnno: u16,
+}
+
+impl FromReader for LineNumber {
+    fn from_reader<R: Read + Seek>(reader: &mut R, endian: Endian) -> Result<Self> {
+        Ok(LineNumber {
+            l_addr: endian.parse_u32(reader)?,
+            l_lnno: endian.parse_u16(reader)?,
+        })
+    }
+}
+
+impl ToWriter for LineNumber {
+    fn to_writer<W: Write>(&self, writer: &mut W, endian: Endian) -> Result<()> {
+        endian.write_u32(writer, self.l_addr)?;
+        endian.write_u16(writer, self.l_lnno)?;
+        Ok(())
+    }
+}
+
+/// A section header bundled with its raw contents, relocations, and line
+/// numbers — everything a disassembler needs to interpret the section.
+#[derive(Debug)]
+pub struct Section {
+    pub header: EcoffSectionHeader,
+    pub data: Vec<u8>,
+    pub relocations: Vec<Relocation>,
+    pub line_numbers: Vec<LineNumber>,
+}
+
+/// The symbolic header together with the symbol records it enumerates and the
+/// local and external string tables used to resolve their names.
+#[derive(Debug)]
+pub struct SymbolTable {
+    pub header: SymbolHeader,
+    pub local_strings: Vec<u8>,
+    pub external_strings: Vec<u8>,
+    pub local_symbols: Vec<Symr>,
+    pub external_symbols: Vec<Extr>,
+    pub file_descriptors: Vec<Fdr>,
+    pub procedure_descriptors: Vec<Pdr>,
+}
+
+impl SymbolTable {
+    /// Resolve a local symbol's name against the local string table.
+    pub fn local_name(&self, symbol: &Symr) -> Result<std::borrow::Cow<'_, str>> {
+        Ok(String::from_utf8_lossy(string_at(
+            &self.local_strings,
+            symbol.iss,
+        )?))
+    }
+
+    /// Resolve an external symbol's name against the external string table.
+    pub fn external_name(&self, symbol: &Extr) -> Result<std::borrow::Cow<'_, str>> {
+        Ok(String::from_utf8_lossy(string_at(
+            &self.external_strings,
+            symbol.asym.iss,
+        )?))
+    }
+}
+
+/// A fully parsed ECOFF object held in memory.
+///
+/// `Ecoff::read` populates every field from a seekable reader; `Ecoff::write`
+/// lays the file back out, recomputing the offsets that describe it. The output
+/// matches the input byte-for-byte only under the conditions listed on
+/// [`Ecoff::write`].
+#[derive(Debug)]
+pub struct Ecoff {
+    pub endian: Endian,
+    pub file_header: EcoffFileHeader,
+    pub optional_header: Option<EcoffOptionalHeader>,
+    pub sections: Vec<Section>,
+    pub symbols: Option<SymbolTable>,
+}
+
+impl Ecoff {
+    /// Parse an entire object file into memory.
+    pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Ecoff> {
+        let endian = detect_endian(reader)?;
+        let file_header = read_record::<_, EcoffFileHeader>(reader, endian)?;
+
+        let optional_header = if file_header.f_opthdr > 0 {
+            Some(read_record::<_, EcoffOptionalHeader>(reader, endian)?)
+        } else {
+            None
+        };
+
+        let mut sections = Vec::with_capacity(file_header.f_nscns as usize);
+        for _ in 0..file_header.f_nscns {
+            let header = read_record::<_, EcoffSectionHeader>(reader, endian)?;
+            let resume = reader.stream_position()?;
+            // A section with no file offset (bss) has no contents on disk.
+            let data = if header.s_scnptr == 0 {
+                Vec::new()
+            } else {
+                read_blob(reader, header.s_scnptr, header.s_size)?
+            };
+
+            let relocations =
+                read_table(reader, endian, header.s_relptr, header.s_nreloc as u32)?;
+            let line_numbers =
+                read_table(reader, endian, header.s_lnnoptr, header.s_nlnno as u32)?;
+
+            reader.seek(SeekFrom::Start(resume))?;
+            sections.push(Section {
+                header,
+                data,
+                relocations,
+                line_numbers,
+            });
+        }
+
+        let symbols = if file_header.f_symptr != 0 && file_header.f_nsyms != 0 {
+            reader.seek(SeekFrom::Start(file_header.f_symptr as u64))?;
+            let header = read_record::<_, SymbolHeader>(reader, endian)?;
+
+            let local_strings = read_blob(reader, header.cb_ss_offset, header.iss_max)?;
+            let external_strings =
+                read_blob(reader, header.cb_ss_ext_offset, header.iss_ext_max)?;
+
+            let local_symbols =
+                read_table(reader, endian, header.cb_sym_offset, header.isym_max)?;
+            let external_symbols =
+                read_table(reader, endian, header.cb_ext_offset, header.iext_max)?;
+            let file_descriptors =
+                read_table(reader, endian, header.cb_fd_offset, header.ifd_max)?;
+            let procedure_descriptors =
+                read_table(reader, endian, header.cb_pd_offset, header.ipd_max)?;
+
+            Some(SymbolTable {
+                header,
+                local_strings,
+                external_strings,
+                local_symbols,
+                external_symbols,
+                file_descriptors,
+                procedure_descriptors,
+            })
+        } else {
+            None
+        };
+
+        Ok(Ecoff {
+            endian,
+            file_header,
+            optional_header,
+            sections,
+            symbols,
+        })
+    }
+
+    /// Write the object back out, recomputing every offset it describes.
+    ///
+    /// The layout is: file header, optional header, section headers, section
+    /// data, each section's relocations and line numbers, then the symbol table
+    /// (symbolic header, the two string tables, and the local/external symbol,
+    /// file-descriptor, and procedure-descriptor arrays). Every `s_scnptr`,
+    /// `s_relptr`, `s_lnnoptr`, `f_symptr`, and the `cb_*_offset` of every emitted
+    /// table is derived from the running cursor. The `SymbolHeader` sub-tables the
+    /// in-memory model does not carry (line, dense-number, optimization, aux, and
+    /// relative-file-descriptor entries) are not emitted and have their counts and
+    /// offsets zeroed rather than left pointing at data that is no longer there.
+    /// An object read with [`Ecoff::read`] is therefore written back byte-for-byte
+    /// only when it has none of those sub-tables and its contents already sit in
+    /// this order with no padding between them; otherwise the output differs.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let endian = self.endian;
+        let opthdr_size = self
+            .optional_header
+            .as_ref()
+            .map_or(0, |_| self.file_header.f_opthdr as u32);
+
+        // Everything after the section headers is laid out contiguously; walk a
+        // cursor through it to assign each blob its on-disk offset.
+        let mut cursor =
+            FILE_HEADER_SIZE + opthdr_size + SECTION_HEADER_SIZE * self.sections.len() as u32;
+
+        // Section data.
+        let mut scnptrs = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let scnptr = if section.data.is_empty() { 0 } else { cursor };
+            scnptrs.push(scnptr);
+            cursor += section.data.len() as u32;
+        }
+
+        // Per-section relocations then line numbers.
+        let mut reloc_blobs = Vec::with_capacity(self.sections.len());
+        let mut line_blobs = Vec::with_capacity(self.sections.len());
+        let mut relptrs = Vec::with_capacity(self.sections.len());
+        let mut lnnoptrs = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let relocs = encode(&section.relocations, endian)?;
+            relptrs.push(if relocs.is_empty() { 0 } else { cursor });
+            cursor += relocs.len() as u32;
+            reloc_blobs.push(relocs);
+
+            let lines = encode(&section.line_numbers, endian)?;
+            lnnoptrs.push(if lines.is_empty() { 0 } else { cursor });
+            cursor += lines.len() as u32;
+            line_blobs.push(lines);
+        }
+
+        // The symbol table follows everything else. Serialize its sub-tables up
+        // front so their recomputed offsets can go into the symbolic header.
+        let symbols = self
+            .symbols
+            .as_ref()
+            .map(|table| -> Result<_> {
+                let symptr = cursor;
+                let cb_ss_offset = symptr + SYMBOL_HEADER_SIZE;
+                let cb_ss_ext_offset = cb_ss_offset + table.local_strings.len() as u32;
+                let cb_sym_offset = cb_ss_ext_offset + table.external_strings.len() as u32;
+
+                let local_syms = encode(&table.local_symbols, endian)?;
+                let cb_ext_offset = cb_sym_offset + local_syms.len() as u32;
+                let external_syms = encode(&table.external_symbols, endian)?;
+                let cb_fd_offset = cb_ext_offset + external_syms.len() as u32;
+                let file_descs = encode(&table.file_descriptors, endian)?;
+                let cb_pd_offset = cb_fd_offset + file_descs.len() as u32;
+                let proc_descs = encode(&table.procedure_descriptors, endian)?;
+
+                let header = SymbolHeader {
+                    // Tables the in-memory model does not carry are dropped.
+                    iline_max: 0,
+                    cb_line: 0,
+                    cb_line_offset: 0,
+                    idn_max: 0,
+                    cb_dn_offset: 0,
+                    iopt_max: 0,
+                    cb_opt_offset: 0,
+                    iaux_max: 0,
+                    cb_aux_offset: 0,
+                    crfd: 0,
+                    cb_rfd_offset: 0,
+                    // Tables we do serialize, with recomputed counts and offsets.
+                    ipd_max: table.procedure_descriptors.len() as u32,
+                    cb_pd_offset,
+                    isym_max: table.local_symbols.len() as u32,
+                    cb_sym_offset,
+                    iss_max: table.local_strings.len() as u32,
+                    cb_ss_offset,
+                    iss_ext_max: table.external_strings.len() as u32,
+                    cb_ss_ext_offset,
+                    ifd_max: table.file_descriptors.len() as u32,
+                    cb_fd_offset,
+                    iext_max: table.external_symbols.len() as u32,
+                    cb_ext_offset,
+                    ..copy_symbol_header(&table.header)
+                };
+                Ok((
+                    symptr,
+                    header,
+                    table,
+                    local_syms,
+                    external_syms,
+                    file_descs,
+                    proc_descs,
+                ))
+            })
+            .transpose()?;
+
+        let mut file_header = EcoffFileHeader {
+            f_magic: self.file_header.f_magic,
+            f_nscns: self.sections.len() as u16,
+            f_timdat: self.file_header.f_timdat,
+            f_symptr: symbols.as_ref().map_or(0, |s| s.0),
+            f_nsyms: self.file_header.f_nsyms,
+            f_opthdr: self.file_header.f_opthdr,
+            f_flags: self.file_header.f_flags,
+        };
+        if self.optional_header.is_none() {
+            file_header.f_opthdr = 0;
+        }
+        file_header.to_writer(writer, endian)?;
+
+        if let Some(optional_header) = &self.optional_header {
+            optional_header.to_writer(writer, endian)?;
+        }
+
+        for (i, section) in self.sections.iter().enumerate() {
+            let header = EcoffSectionHeader {
+                s_name: section.header.s_name,
+                s_paddr: section.header.s_paddr,
+                s_vaddr: section.header.s_vaddr,
+                // Sections without on-disk contents keep the size from their header.
+                s_size: if section.data.is_empty() {
+                    section.header.s_size
+                } else {
+                    section.data.len() as u32
+                },
+                s_scnptr: scnptrs[i],
+                s_relptr: relptrs[i],
+                s_lnnoptr: lnnoptrs[i],
+                s_nreloc: section.relocations.len() as u16,
+                s_nlnno: section.line_numbers.len() as u16,
+                s_flags: section.header.s_flags,
+            };
+            header.to_writer(writer, endian)?;
+        }
+
+        for section in &self.sections {
+            writer.write_all(&section.data)?;
+        }
+
+        for (relocs, lines) in reloc_blobs.iter().zip(&line_blobs) {
+            writer.write_all(relocs)?;
+            writer.write_all(lines)?;
+        }
+
+        if let Some((_, header, table, local_syms, external_syms, file_descs, proc_descs)) = symbols
+        {
+            header.to_writer(writer, endian)?;
+            writer.write_all(&table.local_strings)?;
+            writer.write_all(&table.external_strings)?;
+            writer.write_all(&local_syms)?;
+            writer.write_all(&external_syms)?;
+            writer.write_all(&file_descs)?;
+            writer.write_all(&proc_descs)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serialize a slice of records into a fresh buffer, so callers can measure the
+/// encoded length before deciding where it lands in the file.
+fn encode<T: ToWriter>(records: &[T], endian: Endian) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for record in records {
+        record.to_writer(&mut buf, endian)?;
+    }
+    Ok(buf)
+}
+
+/// Read a single record, tagging an `UnexpectedEof` with the offset it started
+/// at so truncated files report where parsing ran off the end.
+fn read_record<R: Read + Seek, T: FromReader>(reader: &mut R, endian: Endian) -> Result<T> {
+    let offset = reader.stream_position()?;
+    T::from_reader(reader, endian).map_err(|e| e.at(offset))
+}
+
+/// Number of bytes between the current position and the end of the stream,
+/// leaving the cursor where it was. Used to sanity-check header-supplied counts
+/// before allocating so a truncated or hostile file reports a diagnostic instead
+/// of aborting on a multi-gigabyte allocation.
+fn remaining<R: Read + Seek>(reader: &mut R) -> Result<u64> {
+    let pos = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+    Ok(end.saturating_sub(pos))
+}
+
+/// Read a `len`-byte blob starting at `offset`, refusing to allocate more than
+/// the stream actually holds.
+fn read_blob<R: Read + Seek>(reader: &mut R, offset: u32, len: u32) -> Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset as u64))?;
+    if len as u64 > remaining(reader)? {
+        return Err(Error::UnexpectedEof {
+            offset: offset as u64,
+        });
+    }
+    let mut blob = vec![0; len as usize];
+    reader.read_exact(&mut blob)?;
+    Ok(blob)
+}
+
+/// Read `count` records of type `T` starting at byte `offset`.
+///
+/// The records are pushed one at a time rather than pre-reserving `count`
+/// entries, so a bogus count in a malformed header grows the buffer only as far
+/// as the file has bytes to back it instead of attempting a giant allocation up
+/// front.
+fn read_table<R: Read + Seek, T: FromReader>(
+    reader: &mut R,
+    endian: Endian,
+    offset: u32,
+    count: u32,
+) -> Result<Vec<T>> {
+    let mut records = Vec::new();
+    if count != 0 {
+        reader.seek(SeekFrom::Start(offset as u64))?;
+        for _ in 0..count {
+            records.push(read_record(reader, endian)?);
+        }
+    }
+    Ok(records)
+}
+
+/// Clone the scalar fields of a `SymbolHeader` (it is not `Copy` only because
+/// of its size, every field is a plain integer).
+fn copy_symbol_header(header: &SymbolHeader) -> SymbolHeader {
+    SymbolHeader {
+        magic: header.magic,
+        vstamp: header.vstamp,
+        iline_max: header.iline_max,
+        cb_line: header.cb_line,
+        cb_line_offset: header.cb_line_offset,
+        idn_max: header.idn_max,
+        cb_dn_offset: header.cb_dn_offset,
+        ipd_max: header.ipd_max,
+        cb_pd_offset: header.cb_pd_offset,
+        isym_max: header.isym_max,
+        cb_sym_offset: header.cb_sym_offset,
+        iopt_max: header.iopt_max,
+        cb_opt_offset: header.cb_opt_offset,
+        iaux_max: header.iaux_max,
+        cb_aux_offset: header.cb_aux_offset,
+        iss_max: header.iss_max,
+        cb_ss_offset: header.cb_ss_offset,
+        iss_ext_max: header.iss_ext_max,
+        cb_ss_ext_offset: header.cb_ss_ext_offset,
+        ifd_max: header.ifd_max,
+        cb_fd_offset: header.cb_fd_offset,
+        crfd: header.crfd,
+        cb_rfd_offset: header.cb_rfd_offset,
+        iext_max: header.iext_max,
+        cb_ext_offset: header.cb_ext_offset,
+    }
+}
@@ -1,224 +1,204 @@
-use byteorder::{BigEndian, ReadBytesExt};
 use std::fs::File;
-use std::io::{self, BufReader, Read};
-use std::io::{Seek, SeekFrom};
-
-#[derive(Debug)]
-struct EcoffFileHeader {
-    f_magic: u16,
-    f_nscns: u16,
-    f_timdat: u32,
-    f_symptr: u32,
-    f_nsyms: u32,
-    f_opthdr: u16,
-    f_flags: u16,
-}
+use std::io::BufReader;
+use std::path::PathBuf;
 
-#[derive(Debug)]
-struct EcoffOptionalHeader {
-    magic: u16,
-    vstamp: u16,
-    tsize: u32,
-    dsize: u32,
-    bsize: u32,
-    entry: u32,
-    text_start: u32,
-    data_start: u32,
-    bss_start: u32,
-    gprmask: u32,
-    cprmask: [u32; 4],
-    gp_value: u32,
-}
+use chrono::{TimeZone, Utc};
+use clap::{Parser, Subcommand};
 
-fn read_file_header<R: Read>(reader: &mut R) -> io::Result<EcoffFileHeader> {
-    Ok(EcoffFileHeader {
-        f_magic: reader.read_u16::<BigEndian>()?,
-        f_nscns: reader.read_u16::<BigEndian>()?,
-        f_timdat: reader.read_u32::<BigEndian>()?,
-        f_symptr: reader.read_u32::<BigEndian>()?,
-        f_nsyms: reader.read_u32::<BigEndian>()?,
-        f_opthdr: reader.read_u16::<BigEndian>()?,
-        f_flags: reader.read_u16::<BigEndian>()?,
-    })
-}
+use coff::{Ecoff, Result};
 
-fn read_optional_header<R: Read>(reader: &mut R) -> io::Result<EcoffOptionalHeader> {
-    Ok(EcoffOptionalHeader {
-        magic: reader.read_u16::<BigEndian>()?,
-        vstamp: reader.read_u16::<BigEndian>()?,
-        tsize: reader.read_u32::<BigEndian>()?,
-        dsize: reader.read_u32::<BigEndian>()?,
-        bsize: reader.read_u32::<BigEndian>()?,
-        entry: reader.read_u32::<BigEndian>()?,
-        text_start: reader.read_u32::<BigEndian>()?,
-        data_start: reader.read_u32::<BigEndian>()?,
-        bss_start: reader.read_u32::<BigEndian>()?,
-        gprmask: reader.read_u32::<BigEndian>()?,
-        cprmask: [
-            reader.read_u32::<BigEndian>()?,
-            reader.read_u32::<BigEndian>()?,
-            reader.read_u32::<BigEndian>()?,
-            reader.read_u32::<BigEndian>()?,
-        ],
-        gp_value: reader.read_u32::<BigEndian>()?,
-    })
+// https://web.archive.org/web/20160305114748/http://h41361.www4.hp.com/docs/base_doc/DOCUMENTATION/V50A_ACRO_SUP/OBJSPEC.PDF
+#[derive(Parser)]
+#[command(name = "coff", about = "Inspect MIPS ECOFF object files")]
+struct Cli {
+    /// Object file to read.
+    path: PathBuf,
+    #[command(subcommand)]
+    command: Command,
 }
 
-#[derive(Debug)]
-struct EcoffSectionHeader {
-    s_name: [u8; 8],
-    s_paddr: u32,
-    s_vaddr: u32,
-    s_size: u32,
-    s_scnptr: u32,
-    s_relptr: u32,
-    s_lnnoptr: u32,
-    s_nreloc: u16,
-    s_nlnno: u16,
-    s_flags: u32,
+#[derive(Subcommand)]
+enum Command {
+    /// Print the file, optional, and symbolic headers.
+    Headers,
+    /// List the section table.
+    Sections,
+    /// List the local and external symbols.
+    Symbols,
+    /// List per-section relocations.
+    Relocs,
 }
 
-fn read_section_header<R: Read>(reader: &mut R) -> io::Result<EcoffSectionHeader> {
-    let mut s_name = [0u8; 8];
-    reader.read_exact(&mut s_name)?;
-
-    Ok(EcoffSectionHeader {
-        s_name,
-        s_paddr: reader.read_u32::<BigEndian>()?,
-        s_vaddr: reader.read_u32::<BigEndian>()?,
-        s_size: reader.read_u32::<BigEndian>()?,
-        s_scnptr: reader.read_u32::<BigEndian>()?,
-        s_relptr: reader.read_u32::<BigEndian>()?,
-        s_lnnoptr: reader.read_u32::<BigEndian>()?,
-        s_nreloc: reader.read_u16::<BigEndian>()?,
-        s_nlnno: reader.read_u16::<BigEndian>()?,
-        s_flags: reader.read_u32::<BigEndian>()?,
-    })
+/// `f_flags` bits, low to high significance.
+const FILE_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "RELFLG"),
+    (0x0002, "EXEC"),
+    (0x0004, "LNNO"),
+    (0x0008, "LSYMS"),
+    (0x0020, "AR16WR"),
+    (0x0040, "AR32WR"),
+    (0x0080, "AR32W"),
+];
+
+/// `s_flags` section-type bits.
+const SECTION_FLAGS: &[(u32, &str)] = &[
+    (0x0020, "TEXT"),
+    (0x0040, "DATA"),
+    (0x0080, "BSS"),
+    (0x0100, "RDATA"),
+    (0x0200, "SDATA"),
+    (0x0400, "SBSS"),
+    (0x1000, "LIT8"),
+    (0x2000, "LIT4"),
+];
+
+/// Decode a bitmask into its named flags, or `<none>` if none of the named bits
+/// is set. Bits with no entry in `table` are not reported.
+fn decode_flags<M: Into<u32> + Copy>(value: M, table: &[(M, &str)]) -> String {
+    let value = value.into();
+    let names: Vec<&str> = table
+        .iter()
+        .filter(|(mask, _)| value & (*mask).into() != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if names.is_empty() {
+        "<none>".to_string()
+    } else {
+        names.join(" | ")
+    }
 }
 
-fn read_section_data<R: Read + Seek>(
-    reader: &mut BufReader<R>,
-    header: &EcoffSectionHeader,
-) -> io::Result<Vec<u8>> {
-    let mut data = vec![0; header.s_size as usize];
-    reader.seek(SeekFrom::Start(header.s_scnptr as u64))?;
-    reader.read_exact(&mut data)?;
-    Ok(data)
-}
-#[derive(Debug)]
-struct SymbolHeader {
-    magic: u16,
-    vstamp: u16,
-    iline_max: u32,
-    cb_line: u32,
-    cb_line_offset: u32,
-    idn_max: u32,
-    cb_dn_offset: u32,
-    ipd_max: u32,
-    cb_pd_offset: u32,
-    isym_max: u32,
-    cb_sym_offset: u32, // Byte offset to start of local symbols.
-    iopt_max: u32,
-    cb_opt_offset: u32,
-    iaux_max: u32,
-    cb_aux_offset: u32,
-    iss_max: u32, // Byte size of local string table
-    cb_ss_offset: u32,
-    iss_ext_max: u32,
-    cb_ss_ext_offset: u32,
-    ifd_max: u32,
-    cb_fd_offset: u32,
-    crfd: u32,
-    cb_rfd_offset: u32,
-    iext_max: u32,
-    cb_ext_offset: u32,
-    // Machine dependent fields go here if needed
+fn format_timestamp(timdat: u32) -> String {
+    match Utc.timestamp_opt(timdat as i64, 0).single() {
+        Some(date) => date.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => format!("<invalid {timdat}>"),
+    }
 }
 
-fn read_symbol_header<R: Read>(reader: &mut R) -> io::Result<SymbolHeader> {
-    Ok(SymbolHeader {
-        magic: reader.read_u16::<BigEndian>()?,
-        vstamp: reader.read_u16::<BigEndian>()?,
-        iline_max: reader.read_u32::<BigEndian>()?,
-        cb_line: reader.read_u32::<BigEndian>()?,
-        cb_line_offset: reader.read_u32::<BigEndian>()?,
-        idn_max: reader.read_u32::<BigEndian>()?,
-        cb_dn_offset: reader.read_u32::<BigEndian>()?,
-        ipd_max: reader.read_u32::<BigEndian>()?,
-        cb_pd_offset: reader.read_u32::<BigEndian>()?,
-        isym_max: reader.read_u32::<BigEndian>()?,
-        cb_sym_offset: reader.read_u32::<BigEndian>()?,
-        iopt_max: reader.read_u32::<BigEndian>()?,
-        cb_opt_offset: reader.read_u32::<BigEndian>()?,
-        iaux_max: reader.read_u32::<BigEndian>()?,
-        cb_aux_offset: reader.read_u32::<BigEndian>()?,
-        iss_max: reader.read_u32::<BigEndian>()?, // Byte size of local string table.
-        cb_ss_offset: reader.read_u32::<BigEndian>()?, // Byte offset to start of local strings.
-        iss_ext_max: reader.read_u32::<BigEndian>()?, // Byte size of external string table.
-        cb_ss_ext_offset: reader.read_u32::<BigEndian>()?, // Byte offset to start of external strings.
-        ifd_max: reader.read_u32::<BigEndian>()?,
-        cb_fd_offset: reader.read_u32::<BigEndian>()?, // Byte offset to start of file descriptors.
-        crfd: reader.read_u32::<BigEndian>()?,
-        cb_rfd_offset: reader.read_u32::<BigEndian>()?,
-        iext_max: reader.read_u32::<BigEndian>()?, // Number of file descriptors.
-        cb_ext_offset: reader.read_u32::<BigEndian>()?, // Byte offset to start of external strings.
-    })
+fn section_name(name: &[u8; 8]) -> String {
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..end]).into_owned()
 }
 
-// https://web.archive.org/web/20160305114748/http://h41361.www4.hp.com/docs/base_doc/DOCUMENTATION/V50A_ACRO_SUP/OBJSPEC.PDF
-fn main() -> io::Result<()> {
-    let mut file = File::open("/home/d/decomp-toolkit/libapi/a09.o")?;
-    let mut reader = BufReader::new(file);
-
-    // Read the file header
-    let file_header = read_file_header(&mut reader)?;
-    println!("ECOFF File Header: {:?}", file_header);
-
-    // Read the optional header if present
-    if file_header.f_opthdr > 0 {
-        let optional_header = read_optional_header(&mut reader)?;
-        println!("ECOFF Optional Header: {:?}", optional_header);
+fn print_headers(ecoff: &Ecoff) {
+    let fh = &ecoff.file_header;
+    println!("File header:");
+    println!("  magic:     {:#06x}", fh.f_magic);
+    println!("  endian:    {:?}", ecoff.endian);
+    println!("  sections:  {}", fh.f_nscns);
+    println!("  timestamp: {}", format_timestamp(fh.f_timdat));
+    println!("  symbols:   {}", fh.f_nsyms);
+    println!("  flags:     {}", decode_flags(fh.f_flags, FILE_FLAGS));
+
+    if let Some(oh) = &ecoff.optional_header {
+        println!("Optional header:");
+        println!("  magic:     {:#06x}", oh.magic);
+        println!("  text size: {:#x}", oh.tsize);
+        println!("  data size: {:#x}", oh.dsize);
+        println!("  bss size:  {:#x}", oh.bsize);
+        println!("  entry:     {:#x}", oh.entry);
+        println!("  gp value:  {:#x}", oh.gp_value);
     }
 
-    // Read and print each section header
-    for _ in 0..file_header.f_nscns {
-        let section_header = read_section_header(&mut reader)?;
-        let string = String::from_utf8_lossy(&section_header.s_name);
-        println!("{}", string);
-        println!("ECOFF Section Header: {:?}", section_header);
-        let section_data = read_section_data(&mut reader, &section_header)?;
-        println!("section data {:?}", section_data);
+    if let Some(symbols) = &ecoff.symbols {
+        let sh = &symbols.header;
+        println!("Symbolic header:");
+        println!("  local symbols:    {}", sh.isym_max);
+        println!("  external symbols: {}", sh.iext_max);
+        println!("  file descriptors: {}", sh.ifd_max);
+        println!("  proc descriptors: {}", sh.ipd_max);
     }
+}
 
-    reader.seek(SeekFrom::Start(file_header.f_symptr as u64))?;
-
-    let symbol_header = read_symbol_header(&mut reader)?;
-    println!("{:?}", symbol_header);
-
-    {
-        // "The storage format for the string table is a list of null-terminated character strings. It is correctly
-        // considered as one long character array, not an array of strings. Fields in the symbolic header and file
-        // headers represent string table sizes and offsets in bytes."
+fn print_sections(ecoff: &Ecoff) {
+    println!(
+        "{:<10} {:>10} {:>10} {:>10} {:>8}  Flags",
+        "Name", "VAddr", "Size", "FileOff", "Relocs"
+    );
+    for section in &ecoff.sections {
+        let h = &section.header;
+        println!(
+            "{:<10} {:>#10x} {:>#10x} {:>#10x} {:>8}  {}",
+            section_name(&h.s_name),
+            h.s_vaddr,
+            h.s_size,
+            h.s_scnptr,
+            h.s_nreloc,
+            decode_flags(h.s_flags, SECTION_FLAGS),
+        );
+    }
+}
 
-        // read local strings
-        let mut data = vec![0; symbol_header.iss_max as usize];
-        reader.seek(SeekFrom::Start(symbol_header.cb_ss_offset as u64))?;
-        reader.read_exact(&mut data)?;
+fn print_symbols(ecoff: &Ecoff) -> Result<()> {
+    let Some(symbols) = &ecoff.symbols else {
+        println!("no symbol table");
+        return Ok(());
+    };
+
+    println!("Local symbols:");
+    println!("{:>10} {:>4} {:>4}  Name", "Value", "St", "Sc");
+    for symbol in &symbols.local_symbols {
+        println!(
+            "{:>#10x} {:>4} {:>4}  {}",
+            symbol.value,
+            symbol.st,
+            symbol.sc,
+            symbols.local_name(symbol)?,
+        );
+    }
 
-        let string = String::from_utf8_lossy(&data);
+    println!("External symbols:");
+    println!("{:>10} {:>4} {:>4}  Name", "Value", "St", "Sc");
+    for symbol in &symbols.external_symbols {
+        println!(
+            "{:>#10x} {:>4} {:>4}  {}",
+            symbol.asym.value,
+            symbol.asym.st,
+            symbol.asym.sc,
+            symbols.external_name(symbol)?,
+        );
+    }
+    Ok(())
+}
 
-        println!("local strings {:?}", string);
+fn print_relocs(ecoff: &Ecoff) {
+    for section in &ecoff.sections {
+        if section.relocations.is_empty() {
+            continue;
+        }
+        println!("Relocations for {}:", section_name(&section.header.s_name));
+        println!("{:>10} {:>8} {:>6}  Flags", "VAddr", "SymNdx", "Type");
+        for reloc in &section.relocations {
+            let mut flags = Vec::new();
+            if reloc.r_extern {
+                flags.push("extern");
+            }
+            if reloc.r_offset {
+                flags.push("offset");
+            }
+            println!(
+                "{:>#10x} {:>8} {:>6}  {}",
+                reloc.r_vaddr,
+                reloc.r_symndx,
+                reloc.r_type,
+                flags.join(" | "),
+            );
+        }
     }
+}
 
-    {
-        // read external strings
-        let mut data = vec![0; symbol_header.iss_ext_max as usize];
-        reader.seek(SeekFrom::Start(symbol_header.cb_ss_ext_offset as u64))?;
-        reader.read_exact(&mut data)?;
+fn main() -> Result<()> {
+    let cli = Cli::parse();
 
-        let string = String::from_utf8_lossy(&data);
+    let file = File::open(&cli.path)?;
+    let mut reader = BufReader::new(file);
+    let ecoff = Ecoff::read(&mut reader)?;
 
-        println!("external strings {:?}", string);
+    match cli.command {
+        Command::Headers => print_headers(&ecoff),
+        Command::Sections => print_sections(&ecoff),
+        Command::Symbols => print_symbols(&ecoff)?,
+        Command::Relocs => print_relocs(&ecoff),
     }
 
     Ok(())
@@ -0,0 +1,195 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::{
+    detect_endian, read_record, EcoffFileHeader, EcoffOptionalHeader, EcoffSectionHeader, Endian,
+    Extr, Fdr, Pdr, Result, SymbolHeader, Symr,
+};
+
+/// A record yielded while walking an object file front to back.
+///
+/// The singleton headers are reported as markers; the repeated symbol-table
+/// entries carry their parsed record so consumers can process arbitrarily large
+/// tables without the whole file in memory.
+#[derive(Debug)]
+pub enum Record {
+    FileHeader,
+    OptionalHeader,
+    SectionHeader(EcoffSectionHeader),
+    SymbolHeader,
+    LocalSymbol(Symr),
+    ExternalSymbol(Extr),
+    FileDescriptor(Fdr),
+    ProcedureDescriptor(Pdr),
+    EndOfSymbols,
+}
+
+/// Internal state-machine position, advanced one record at a time.
+enum Phase {
+    FileHeader,
+    OptionalHeader,
+    Sections,
+    SymbolHeader,
+    LocalSymbols,
+    ExternalSymbols,
+    FileDescriptors,
+    ProcedureDescriptors,
+    EndOfSymbols,
+    Done,
+}
+
+/// A lazy reader that yields [`Record`]s from a seekable object file.
+///
+/// Modeled on the pspp `Record` state machine: each call to `next` reads exactly
+/// one record and advances the phase, seeking to the next table only when the
+/// current one is exhausted.
+pub struct RecordReader<R: Read + Seek> {
+    reader: R,
+    endian: Endian,
+    phase: Phase,
+    has_optional: bool,
+    sections_left: u16,
+    has_symbols: bool,
+    symptr: u32,
+    // Populated once the symbolic header is read.
+    sym_offset: u32,
+    ext_offset: u32,
+    fd_offset: u32,
+    pd_offset: u32,
+    local_left: u32,
+    external_left: u32,
+    fd_left: u32,
+    pd_left: u32,
+}
+
+impl<R: Read + Seek> RecordReader<R> {
+    /// Create a reader positioned at the start of `reader`, detecting the byte
+    /// order from `f_magic`.
+    pub fn new(mut reader: R) -> Result<RecordReader<R>> {
+        reader.seek(SeekFrom::Start(0))?;
+        let endian = detect_endian(&mut reader)?;
+        Ok(RecordReader {
+            reader,
+            endian,
+            phase: Phase::FileHeader,
+            has_optional: false,
+            sections_left: 0,
+            has_symbols: false,
+            symptr: 0,
+            sym_offset: 0,
+            ext_offset: 0,
+            fd_offset: 0,
+            pd_offset: 0,
+            local_left: 0,
+            external_left: 0,
+            fd_left: 0,
+            pd_left: 0,
+        })
+    }
+
+    fn advance(&mut self) -> Result<Option<Record>> {
+        let endian = self.endian;
+        loop {
+            match self.phase {
+                Phase::FileHeader => {
+                    let header = read_record::<_, EcoffFileHeader>(&mut self.reader, endian)?;
+                    self.has_optional = header.f_opthdr > 0;
+                    self.sections_left = header.f_nscns;
+                    self.symptr = header.f_symptr;
+                    self.has_symbols = header.f_symptr != 0 && header.f_nsyms != 0;
+                    self.phase = Phase::OptionalHeader;
+                    return Ok(Some(Record::FileHeader));
+                }
+                Phase::OptionalHeader => {
+                    self.phase = Phase::Sections;
+                    if self.has_optional {
+                        read_record::<_, EcoffOptionalHeader>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::OptionalHeader));
+                    }
+                }
+                Phase::Sections => {
+                    if self.sections_left > 0 {
+                        self.sections_left -= 1;
+                        let header =
+                            read_record::<_, EcoffSectionHeader>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::SectionHeader(header)));
+                    }
+                    self.phase = Phase::SymbolHeader;
+                }
+                Phase::SymbolHeader => {
+                    if !self.has_symbols {
+                        self.phase = Phase::EndOfSymbols;
+                        continue;
+                    }
+                    self.reader.seek(SeekFrom::Start(self.symptr as u64))?;
+                    let header = read_record::<_, SymbolHeader>(&mut self.reader, endian)?;
+                    self.sym_offset = header.cb_sym_offset;
+                    self.ext_offset = header.cb_ext_offset;
+                    self.fd_offset = header.cb_fd_offset;
+                    self.pd_offset = header.cb_pd_offset;
+                    self.local_left = header.isym_max;
+                    self.external_left = header.iext_max;
+                    self.fd_left = header.ifd_max;
+                    self.pd_left = header.ipd_max;
+                    self.reader.seek(SeekFrom::Start(self.sym_offset as u64))?;
+                    self.phase = Phase::LocalSymbols;
+                    return Ok(Some(Record::SymbolHeader));
+                }
+                Phase::LocalSymbols => {
+                    if self.local_left > 0 {
+                        self.local_left -= 1;
+                        let record = read_record::<_, Symr>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::LocalSymbol(record)));
+                    }
+                    self.reader.seek(SeekFrom::Start(self.ext_offset as u64))?;
+                    self.phase = Phase::ExternalSymbols;
+                }
+                Phase::ExternalSymbols => {
+                    if self.external_left > 0 {
+                        self.external_left -= 1;
+                        let record = read_record::<_, Extr>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::ExternalSymbol(record)));
+                    }
+                    self.reader.seek(SeekFrom::Start(self.fd_offset as u64))?;
+                    self.phase = Phase::FileDescriptors;
+                }
+                Phase::FileDescriptors => {
+                    if self.fd_left > 0 {
+                        self.fd_left -= 1;
+                        let record = read_record::<_, Fdr>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::FileDescriptor(record)));
+                    }
+                    self.reader.seek(SeekFrom::Start(self.pd_offset as u64))?;
+                    self.phase = Phase::ProcedureDescriptors;
+                }
+                Phase::ProcedureDescriptors => {
+                    if self.pd_left > 0 {
+                        self.pd_left -= 1;
+                        let record = read_record::<_, Pdr>(&mut self.reader, endian)?;
+                        return Ok(Some(Record::ProcedureDescriptor(record)));
+                    }
+                    self.phase = Phase::EndOfSymbols;
+                }
+                Phase::EndOfSymbols => {
+                    self.phase = Phase::Done;
+                    return Ok(Some(Record::EndOfSymbols));
+                }
+                Phase::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for RecordReader<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => None,
+            Err(e) => {
+                self.phase = Phase::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}